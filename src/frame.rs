@@ -0,0 +1,393 @@
+//! The frame module wraps the [`crate::lz77`] block codec in the standard LZ4 frame
+//! container, so output produced here can be read by the reference `lz4` CLI and other
+//! conforming implementations, and frames produced elsewhere can be read back as long as
+//! their blocks are independent and they carry no dictionary ID.
+//!
+//! A frame is a 4-byte magic number, a frame descriptor (FLG/BD bytes, an optional content
+//! size, and a header checksum byte), zero or more length-prefixed data blocks, a 4-byte
+//! zero `EndMark`, and an optional content checksum.
+
+use thiserror::Error as ThisErr;
+
+use crate::lz77::{compress, decode_block, encode_block, decompress, CompressionSettings, DecompressionSettings, Error as BlockError};
+use crate::xxhash::xxhash32;
+
+const MAGIC_NUMBER: u32 = 0x184D2204;
+
+/// The maximum size of an individual uncompressed data block within a frame.
+///
+/// This is encoded in the 3-bit "block maximum size" field of the BD byte; LZ4 only
+/// defines codes 4 through 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSize {
+    /// Blocks of at most 64 KiB.
+    Max64KB,
+    /// Blocks of at most 256 KiB.
+    Max256KB,
+    /// Blocks of at most 1 MiB.
+    Max1MB,
+    /// Blocks of at most 4 MiB.
+    Max4MB,
+}
+
+impl BlockSize {
+    fn code(self) -> u8 {
+        match self {
+            BlockSize::Max64KB => 4,
+            BlockSize::Max256KB => 5,
+            BlockSize::Max1MB => 6,
+            BlockSize::Max4MB => 7,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Self, FrameError> {
+        match code {
+            4 => Ok(BlockSize::Max64KB),
+            5 => Ok(BlockSize::Max256KB),
+            6 => Ok(BlockSize::Max1MB),
+            7 => Ok(BlockSize::Max4MB),
+            other => Err(FrameError::InvalidBlockSizeCode(other)),
+        }
+    }
+
+    /// The maximum number of uncompressed bytes a single block of this size may hold.
+    pub fn max_bytes(self) -> usize {
+        match self {
+            BlockSize::Max64KB => 64 * 1024,
+            BlockSize::Max256KB => 256 * 1024,
+            BlockSize::Max1MB => 1024 * 1024,
+            BlockSize::Max4MB => 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl Default for BlockSize {
+    fn default() -> Self {
+        BlockSize::Max4MB
+    }
+}
+
+/// Errors that can occur while decoding an LZ4 frame.
+#[derive(ThisErr, Debug, Clone)]
+pub enum FrameError {
+    /// The frame did not start with the LZ4 magic number.
+    #[error("Invalid magic number: {0:#010x}")]
+    InvalidMagicNumber(u32),
+    /// The BD byte named a block-maximum-size code outside 4..=7.
+    #[error("Invalid block size code: {0}")]
+    InvalidBlockSizeCode(u8),
+    /// The frame descriptor's header checksum byte didn't match its contents.
+    #[error("Frame descriptor header checksum mismatch")]
+    InvalidHeaderChecksum,
+    /// The input ended before a complete frame could be read, or a block's size field
+    /// exceeded the frame's block maximum size.
+    #[error("Frame data was truncated at byte {idx}")]
+    Truncated {
+        /// The byte offset at which the truncation was detected.
+        idx: usize,
+    },
+    /// A block's checksum didn't match its contents.
+    #[error("Block checksum mismatch")]
+    BlockChecksumMismatch,
+    /// The content checksum at the end of the frame didn't match the decompressed output.
+    #[error("Content checksum mismatch")]
+    ContentChecksumMismatch,
+    /// The block codec failed to decode a block's contents.
+    #[error("Failed to decode block: {0}")]
+    Block(#[from] BlockError),
+}
+
+/// Builds LZ4 frames around [`crate::lz77`]'s block codec.
+///
+/// All blocks produced by a `FrameEncoder` are independent (no block may reference
+/// match data from a previous block), matching the conservative default used by the
+/// reference implementation.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameEncoder {
+    block_size: BlockSize,
+    block_checksums: bool,
+    content_checksum: bool,
+}
+
+impl Default for FrameEncoder {
+    fn default() -> Self {
+        FrameEncoder {
+            block_size: BlockSize::default(),
+            block_checksums: false,
+            content_checksum: false,
+        }
+    }
+}
+
+impl FrameEncoder {
+    /// Creates a new encoder with the default block size and no checksums enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of each data block.
+    pub fn block_size(mut self, block_size: BlockSize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Enables or disables a per-block xxHash32 checksum.
+    pub fn block_checksums(mut self, enabled: bool) -> Self {
+        self.block_checksums = enabled;
+        self
+    }
+
+    /// Enables or disables a whole-content xxHash32 checksum following the `EndMark`.
+    pub fn content_checksum(mut self, enabled: bool) -> Self {
+        self.content_checksum = enabled;
+        self
+    }
+
+    fn flg_byte(&self) -> u8 {
+        let version = 0b01 << 6;
+        let block_independence = 1 << 5;
+        let block_checksum = (self.block_checksums as u8) << 4;
+        let content_checksum = (self.content_checksum as u8) << 2;
+        version | block_independence | block_checksum | content_checksum
+    }
+
+    fn bd_byte(&self) -> u8 {
+        self.block_size.code() << 4
+    }
+
+    /// Encodes `data` into a complete LZ4 frame, compressing each block with `settings`.
+    pub fn encode(&self, data: &[u8], settings: CompressionSettings) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC_NUMBER.to_le_bytes());
+
+        let descriptor = [self.flg_byte(), self.bd_byte()];
+        let header_checksum = ((xxhash32(&descriptor, 0) >> 8) & 0xFF) as u8;
+        out.extend_from_slice(&descriptor);
+        out.push(header_checksum);
+
+        for chunk in data.chunks(self.block_size.max_bytes()) {
+            let tokens = compress(chunk, settings);
+            let compressed = encode_block(&tokens);
+
+            let (block_data, stored_uncompressed) = if compressed.len() < chunk.len() {
+                (compressed, false)
+            } else {
+                (chunk.to_vec(), true)
+            };
+
+            let mut size_field = block_data.len() as u32;
+            if stored_uncompressed {
+                size_field |= 0x8000_0000;
+            }
+            out.extend_from_slice(&size_field.to_le_bytes());
+            out.extend_from_slice(&block_data);
+
+            if self.block_checksums {
+                out.extend_from_slice(&xxhash32(&block_data, 0).to_le_bytes());
+            }
+        }
+
+        out.extend_from_slice(&0u32.to_le_bytes()); // EndMark
+
+        if self.content_checksum {
+            out.extend_from_slice(&xxhash32(data, 0).to_le_bytes());
+        }
+
+        out
+    }
+}
+
+/// Reads LZ4 frames produced by [`FrameEncoder`] (or any conforming implementation).
+///
+/// Each block is decoded on its own, so frames with linked blocks (block independence flag
+/// clear) are not supported; neither are frames with the dictionary ID flag set, since the
+/// dictionary ID field is not read.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameDecoder {
+    block_checksums: bool,
+    content_checksum: bool,
+}
+
+impl FrameDecoder {
+    /// Creates a new decoder. Checksum expectations are read from the frame itself;
+    /// these fields exist only so a caller can inspect what the last decode found.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the most recently decoded frame carried per-block checksums.
+    pub fn had_block_checksums(&self) -> bool {
+        self.block_checksums
+    }
+
+    /// Whether the most recently decoded frame carried a content checksum.
+    pub fn had_content_checksum(&self) -> bool {
+        self.content_checksum
+    }
+
+    /// Decodes a complete LZ4 frame, verifying any checksums it contains.
+    pub fn decode(&mut self, data: &[u8], settings: DecompressionSettings) -> Result<Vec<u8>, FrameError> {
+        let mut pos = 0;
+
+        let magic = read_u32(data, &mut pos)?;
+        if magic != MAGIC_NUMBER {
+            return Err(FrameError::InvalidMagicNumber(magic));
+        }
+
+        let descriptor_start = pos;
+        let flg = read_u8(data, &mut pos)?;
+        let bd = read_u8(data, &mut pos)?;
+
+        let block_checksums = flg & 0b0001_0000 != 0;
+        let content_checksum = flg & 0b0000_0100 != 0;
+        let content_size_present = flg & 0b0000_1000 != 0;
+
+        if content_size_present {
+            let _content_size = read_u64(data, &mut pos)?;
+        }
+
+        let block_size = BlockSize::from_code((bd >> 4) & 0b0111)?;
+        let descriptor_end = pos;
+
+        let header_checksum = read_u8(data, &mut pos)?;
+        let expected = ((xxhash32(&data[descriptor_start..descriptor_end], 0) >> 8) & 0xFF) as u8;
+        if header_checksum != expected {
+            return Err(FrameError::InvalidHeaderChecksum);
+        }
+
+        self.block_checksums = block_checksums;
+        self.content_checksum = content_checksum;
+
+        let mut output = Vec::new();
+        loop {
+            let size_field = read_u32(data, &mut pos)?;
+            if size_field == 0 {
+                break; // EndMark
+            }
+
+            let stored_uncompressed = size_field & 0x8000_0000 != 0;
+            let block_len = (size_field & 0x7FFF_FFFF) as usize;
+            if block_len > block_size.max_bytes() {
+                return Err(FrameError::Truncated { idx: pos });
+            }
+
+            let block_data = data
+                .get(pos..pos + block_len)
+                .ok_or(FrameError::Truncated { idx: pos })?;
+            pos += block_len;
+
+            if block_checksums {
+                let checksum = read_u32(data, &mut pos)?;
+                if xxhash32(block_data, 0) != checksum {
+                    return Err(FrameError::BlockChecksumMismatch);
+                }
+            }
+
+            if stored_uncompressed {
+                output.extend_from_slice(block_data);
+            } else {
+                let tokens = decode_block(block_data)?;
+                let decompressed = decompress(tokens.into_iter(), settings)?;
+                output.extend_from_slice(&decompressed);
+            }
+        }
+
+        if content_checksum {
+            let checksum = read_u32(data, &mut pos)?;
+            if xxhash32(&output, 0) != checksum {
+                return Err(FrameError::ContentChecksumMismatch);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+fn read_u8(data: &[u8], pos: &mut usize) -> Result<u8, FrameError> {
+    let b = *data.get(*pos).ok_or(FrameError::Truncated { idx: *pos })?;
+    *pos += 1;
+    Ok(b)
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> Result<u32, FrameError> {
+    let bytes = data
+        .get(*pos..*pos + 4)
+        .ok_or(FrameError::Truncated { idx: *pos })?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], pos: &mut usize) -> Result<u64, FrameError> {
+    let bytes = data
+        .get(*pos..*pos + 8)
+        .ok_or(FrameError::Truncated { idx: *pos })?;
+    *pos += 8;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_without_checksums() {
+        let data = b"abcabcabcabcabcabcabcabc LZ4 frame round trip abcabcabc";
+        let encoded = FrameEncoder::new().encode(data, CompressionSettings::lz4_default());
+
+        let decoded = FrameDecoder::new()
+            .decode(&encoded, DecompressionSettings::default())
+            .unwrap();
+
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn round_trip_with_checksums() {
+        let data = std::iter::repeat(b"the quick brown fox jumps over the lazy dog. " as &[u8])
+            .take(50)
+            .flatten()
+            .copied()
+            .collect::<Vec<u8>>();
+
+        let encoded = FrameEncoder::new()
+            .block_size(BlockSize::Max64KB)
+            .block_checksums(true)
+            .content_checksum(true)
+            .encode(&data, CompressionSettings::lz4_default());
+
+        let mut decoder = FrameDecoder::new();
+        let decoded = decoder.decode(&encoded, DecompressionSettings::default()).unwrap();
+
+        assert_eq!(decoded, data);
+        assert!(decoder.had_block_checksums());
+        assert!(decoder.had_content_checksum());
+    }
+
+    #[test]
+    fn rejects_bad_magic_number() {
+        let err = FrameDecoder::new()
+            .decode(&[0, 0, 0, 0], DecompressionSettings::default())
+            .unwrap_err();
+        assert!(matches!(err, FrameError::InvalidMagicNumber(0)));
+    }
+
+    #[test]
+    fn rejects_corrupted_block_checksum() {
+        let data = b"abcabcabcabcabcabcabcabc";
+        let mut encoded = FrameEncoder::new()
+            .block_checksums(true)
+            .encode(data, CompressionSettings::lz4_default());
+
+        // Flip every bit of the first byte of the first block's data.
+        let corrupt_idx = 11; // past magic (4) + descriptor (2) + checksum (1) + block size (4)
+        encoded[corrupt_idx] ^= 0xFF;
+
+        let err = FrameDecoder::new()
+            .decode(&encoded, DecompressionSettings::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FrameError::BlockChecksumMismatch | FrameError::Block(_)
+        ));
+    }
+}
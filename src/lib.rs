@@ -4,8 +4,11 @@
 //! An implementation of the [LZ4 algorithm]
 //! [LZ4 algorithm]: https://github.com/lz4/lz4/wiki
 
+pub mod frame;
 pub mod lz77;
 mod matchmap;
+pub mod stream;
+mod xxhash;
 
 #[cfg(test)]
 mod tests {
@@ -1,9 +1,7 @@
 //! The lz77 module contains an implementation of the LZ77 compression/decompression algorithm.
 
-use fallible_iterator::FallibleIterator;
 use thiserror::Error as ThisErr;
-use std::collections::{VecDeque, HashMap};
-use crate::matchmap::MatchMap;
+use crate::matchmap::{match_length, HashChain, MatchMap};
 
 
 /// Represents an LZ77 token.
@@ -55,41 +53,216 @@ pub enum Error {
     /// Either the input or output was too large.
     #[error("The output exceeded a maximum size specification.")]
     MaximumSizeExceeded,
+    /// The encoded block ended before a complete sequence could be read.
+    #[error("Block data was truncated at byte {idx}")]
+    TruncatedBlock {
+        /// The byte offset into the block at which the truncation was detected.
+        idx: usize,
+    },
+}
+
+/// The minimum match length fixed by the LZ4 block format; a token's match-length nibble
+/// encodes `length - MINMATCH`.
+const MINMATCH: usize = 4;
+
+/// Writes an LZ4 "linear small integer code" (LSIC) extension: repeated `0xFF` bytes followed
+/// by a final byte strictly less than `0xFF`, summing to `value`.
+fn write_lsic(value: usize, out: &mut Vec<u8>) {
+    let mut remaining = value;
+    while remaining >= 0xFF {
+        out.push(0xFF);
+        remaining -= 0xFF;
+    }
+    out.push(remaining as u8);
+}
+
+/// Reads an LSIC extension starting at `*pos`, advancing `*pos` past it.
+fn read_lsic(data: &[u8], pos: &mut usize) -> Result<usize, Error> {
+    let mut sum = 0usize;
+    loop {
+        let b = *data.get(*pos).ok_or(Error::TruncatedBlock { idx: *pos })?;
+        *pos += 1;
+        sum += b as usize;
+        if b != 0xFF {
+            break;
+        }
+    }
+    Ok(sum)
+}
+
+/// Encodes a sequence of [`Token`]s into the real LZ4 block wire format: alternating
+/// literal runs and matches, each sequence led by a token byte whose high nibble is the
+/// literal count and low nibble is `match_length - MINMATCH` (with LSIC extensions when a
+/// nibble saturates at 15), literals, a 2-byte little-endian match offset, and match-length
+/// LSIC extension. The final sequence in the block is always literals-only.
+///
+/// Every [`Token::Match`] in `tokens` must have `length >= MINMATCH` and `offset` no greater
+/// than `u16::MAX`, as the LZ4 block format has no way to express either a shorter match or
+/// a wider offset field; [`compress`] never emits a token violating either. This is checked
+/// and panics rather than silently wrapping or truncating.
+pub fn encode_block(tokens: &[Token]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut literals: Vec<u8> = Vec::new();
+
+    for token in tokens {
+        match *token {
+            Token::Literal(b) => literals.push(b),
+            Token::Match { offset, length } => {
+                let lit_len = literals.len();
+                assert!(length >= MINMATCH, "Token::Match length {length} must be >= MINMATCH");
+                let match_len = length - MINMATCH;
+
+                let token_byte = ((lit_len.min(15) as u8) << 4) | (match_len.min(15) as u8);
+                out.push(token_byte);
+                if lit_len >= 15 {
+                    write_lsic(lit_len - 15, &mut out);
+                }
+                out.extend_from_slice(&literals);
+                literals.clear();
+
+                let offset = u16::try_from(offset)
+                    .unwrap_or_else(|_| panic!("Token::Match offset {offset} does not fit in the 16-bit LZ4 block offset field"));
+                out.extend_from_slice(&offset.to_le_bytes());
+                if match_len >= 15 {
+                    write_lsic(match_len - 15, &mut out);
+                }
+            }
+        }
+    }
+
+    // The block always ends with a literals-only sequence, even if it's empty.
+    let lit_len = literals.len();
+    out.push((lit_len.min(15) as u8) << 4);
+    if lit_len >= 15 {
+        write_lsic(lit_len - 15, &mut out);
+    }
+    out.extend_from_slice(&literals);
+
+    out
+}
+
+/// Decodes a block produced by [`encode_block`] back into its [`Token`]s.
+pub fn decode_block(data: &[u8]) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let token_byte = data[pos];
+        pos += 1;
+
+        let mut lit_len = (token_byte >> 4) as usize;
+        let match_nibble = (token_byte & 0x0F) as usize;
+        if lit_len == 15 {
+            lit_len += read_lsic(data, &mut pos)?;
+        }
+
+        let lit_end = pos
+            .checked_add(lit_len)
+            .filter(|&e| e <= data.len())
+            .ok_or(Error::TruncatedBlock { idx: pos })?;
+        tokens.extend(data[pos..lit_end].iter().map(|&b| Token::Literal(b)));
+        pos = lit_end;
+
+        if pos >= data.len() {
+            // Last sequence in the block: literals only, no trailing offset/match-length.
+            break;
+        }
+
+        let offset_end = pos
+            .checked_add(2)
+            .filter(|&e| e <= data.len())
+            .ok_or(Error::TruncatedBlock { idx: pos })?;
+        let offset = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos = offset_end;
+
+        let mut match_len = match_nibble + MINMATCH;
+        if match_nibble == 15 {
+            match_len += read_lsic(data, &mut pos)?;
+        }
+
+        tokens.push(Token::new_match(offset, match_len));
+    }
+
+    Ok(tokens)
 }
 
 /// Represents settings for the LZ77 algorithm, compression direction.
+#[derive(Clone, Copy)]
 pub struct CompressionSettings {
     max_match_offset: usize,
     min_match_len: usize,
     end_literals: usize,
+    match_strategy: MatchStrategy,
+}
+
+/// Selects how [`Compressor`] searches for matches.
+#[derive(Debug, Clone, Copy)]
+enum MatchStrategy {
+    /// A single candidate per hash bucket, taken greedily. Cheap and fixed-memory.
+    Fast,
+    /// LZ4HC-style: walk a hash chain of candidates (bounded by `max_chain_length`) to find
+    /// the longest match, with one position of lazy lookahead.
+    Hc {
+        max_chain_length: usize,
+    },
 }
 
 impl Default for CompressionSettings {
     fn default() -> Self {
         CompressionSettings {
-            max_match_offset: 64 * 1024,
+            // LZ4 match offsets are encoded as a 2-byte little-endian field, so 0xFFFF is the
+            // furthest a match can legally point back; both matchers reject anything farther.
+            max_match_offset: 0xFFFF,
             min_match_len: 4,
             end_literals: 12,
+            match_strategy: MatchStrategy::Fast,
         }
     }
 }
 
 impl CompressionSettings {
     /// Default compression settings for the LZ4 algorithm
-    /// Minimum match of 4, 12 literals at the end, max match offset of 64KiB
+    /// Minimum match of 4, 12 literals at the end, max match offset of 0xFFFF (the largest
+    /// value the 2-byte LZ4 offset field can hold)
     pub fn lz4_default() -> Self {
         Self::default()
     }
 
+    /// High-compression settings: trades CPU for ratio by walking a hash chain of match
+    /// candidates (instead of taking the first one) and evaluating one position of lazy
+    /// lookahead before committing to a match. `level` is clamped to `1..=12`, as in the
+    /// reference LZ4HC implementation; higher levels search longer chains.
+    pub fn lz4_hc(level: u32) -> Self {
+        let level = level.clamp(1, 12);
+        let mut out = Self::default();
+        out.match_strategy = MatchStrategy::Hc {
+            max_chain_length: 1usize << level,
+        };
+        out
+    }
+
     #[doc(hidden)]
     pub(crate) fn test_default() -> Self {
         let mut out = Self::default();
         out.end_literals = 0;
         out
     }
+
+    #[doc(hidden)]
+    pub(crate) fn test_default_hc() -> Self {
+        let mut out = Self::lz4_hc(6);
+        out.end_literals = 0;
+        out
+    }
+
+    /// The furthest back a match is allowed to point, in bytes.
+    pub(crate) fn max_match_offset(&self) -> usize {
+        self.max_match_offset
+    }
 }
 
 /// Represents settings for the LZ77 algorithm, decompression direction.
+#[derive(Clone, Copy)]
 pub struct DecompressionSettings {
     max_output_len: usize
 }
@@ -104,10 +277,57 @@ impl Default for DecompressionSettings {
     }
 }
 
+impl DecompressionSettings {
+    /// The largest total output a decompression may produce before failing with
+    /// [`Error::MaximumSizeExceeded`].
+    pub(crate) fn max_output_len(&self) -> usize {
+        self.max_output_len
+    }
+}
+
+/// The match finder backing a [`Compressor`], picked based on `settings.match_strategy`.
+enum Matcher {
+    Fast(MatchMap),
+    Hc {
+        chain: HashChain,
+        max_chain_length: usize,
+    },
+}
+
+impl Matcher {
+    fn new(settings: &CompressionSettings) -> Self {
+        match settings.match_strategy {
+            MatchStrategy::Fast => Matcher::Fast(MatchMap::new(settings.max_match_offset)),
+            MatchStrategy::Hc { max_chain_length } => Matcher::Hc {
+                chain: HashChain::new(settings.max_match_offset),
+                max_chain_length,
+            },
+        }
+    }
+
+    /// Finds the best match at `idx`, returning its starting position and length.
+    fn get_match(&self, data: &[u8], idx: usize) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Fast(m) => m.get_match(data, idx).map(|pos| (pos, match_length(data, idx, pos))),
+            Matcher::Hc { chain, max_chain_length } => chain.best_match(data, idx, *max_chain_length),
+        }
+    }
+
+    fn add_prefix(&mut self, data: &[u8], idx: usize) {
+        match self {
+            Matcher::Fast(m) => m.add_prefix(data, idx),
+            Matcher::Hc { chain, .. } => chain.add_prefix(data, idx),
+        }
+    }
+}
+
 pub struct Compressor<'a> {
     data: &'a [u8],
     idx: usize,
-    matches: MatchMap<'a>,
+    matches: Matcher,
+    /// A match deferred by one position during lazy matching (see [`MatchStrategy::Hc`]);
+    /// emitted, and its length applied to `idx`, on the following call to `next`.
+    pending: Option<Token>,
     settings: CompressionSettings,
 }
 
@@ -119,32 +339,53 @@ pub struct Decompressor<T: Iterator<Item=Token>> {
 
 impl Compressor<'_> {
     pub fn new(data: &[u8], settings: CompressionSettings) -> Compressor<'_> {
-        Compressor { data, idx: 0, matches: MatchMap::new(settings.max_match_offset), settings }
+        Self::with_start(data, 0, settings)
+    }
+
+    /// Creates a compressor over `data` that begins emitting tokens at `start_idx`, but still
+    /// seeds the match finder with every position before it. Used by streaming compression to
+    /// let matches in newly-pushed data reference back into a retained dictionary window
+    /// without re-emitting tokens for that window.
+    pub(crate) fn with_start(data: &[u8], start_idx: usize, settings: CompressionSettings) -> Compressor<'_> {
+        let mut matches = Matcher::new(&settings);
+        let mut seed_idx = 0;
+        while seed_idx < start_idx && data.len().saturating_sub(seed_idx) > settings.min_match_len {
+            matches.add_prefix(data, seed_idx);
+            seed_idx += 1;
+        }
+        Compressor { data, idx: start_idx, matches, pending: None, settings }
     }
 
     pub fn reset(&mut self) {
-        self.idx = 0
+        self.idx = 0;
+        self.pending = None;
     }
 
-    fn get_match(&self) -> Option<Token> {
-        let dist_from_end = self.data.len() - self.idx;
-        if dist_from_end > self.settings.min_match_len && dist_from_end > self.settings.end_literals {
-            let match_idx = self.matches.get_match(&self.data[self.idx..(self.idx + self.settings.min_match_len)]);
-            match_idx.map(|i| Token::new_match(self.idx - i, self.determine_match_length(i)))
-        } else {
-            None
-        }
+    /// Whether a match search may be attempted at `idx`: more bytes remain than a
+    /// minimum-length match needs, and more than the trailing literals the settings require.
+    /// The two bounds are checked separately, not summed.
+    fn can_search(&self, idx: usize) -> bool {
+        let dist_from_end = self.data.len() - idx;
+        dist_from_end > self.settings.min_match_len && dist_from_end > self.settings.end_literals
     }
 
-    fn determine_match_length(&self, match_idx: usize) -> usize {
-        self.data[self.idx..].iter()
-            .zip(self.data[match_idx..].iter())
-            .take_while(|(&a, &b)| a == b).count()
+    /// Finds a match at `idx`, capping its length so at least `end_literals` bytes remain as
+    /// literals at the end of the block. A match that can't be shortened to at least
+    /// `min_match_len` while leaving that trailing room is rejected outright, since `can_search`
+    /// checks the match and trailing-literal bounds separately rather than together.
+    fn find_match(&self, idx: usize) -> Option<(usize, usize)> {
+        let (pos, len) = self.matches.get_match(self.data, idx)?;
+
+        let max_len = self.data.len() - idx - self.settings.end_literals;
+        if max_len < self.settings.min_match_len {
+            return None;
+        }
+
+        Some((pos, len.min(max_len)))
     }
 
     fn advance(&mut self, amt: usize) {
         self.idx += amt;
-        self.matches.advance(self.idx);
     }
 }
 
@@ -152,22 +393,56 @@ impl Iterator for Compressor<'_> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let out = if self.idx >= self.data.len() {
-            None
-        } else if let Some(m) = self.get_match() {
-            Some(m)
+        if let Some(token) = self.pending.take() {
+            self.advance(token.token_len());
+            return Some(token);
+        }
+
+        if self.idx >= self.data.len() {
+            return None;
+        }
+
+        let candidate = if self.can_search(self.idx) {
+            self.find_match(self.idx)
         } else {
-            let o = self.data[self.idx];
-            Some(Token::Literal(o))
+            None
         };
 
         if self.data.len().saturating_sub(self.idx) > self.settings.min_match_len {
-            self.matches.add_prefix(&self.data[self.idx..(self.idx + self.settings.min_match_len)], self.idx);
+            self.matches.add_prefix(self.data, self.idx);
+        }
+
+        let token = match candidate {
+            None => Token::Literal(self.data[self.idx]),
+            Some((pos, len)) => {
+                // Lazy matching (LZ4HC-style): before committing to this match, see whether
+                // waiting one position yields a strictly longer one. The fast strategy skips
+                // this extra lookup to stay cheap, taking the first match it finds.
+                let is_hc = matches!(self.matches, Matcher::Hc { .. });
+                let next_idx = self.idx + 1;
+                let lookahead = if is_hc && next_idx < self.data.len() && self.can_search(next_idx) {
+                    self.find_match(next_idx)
+                } else {
+                    None
+                };
+
+                if is_hc && self.data.len().saturating_sub(next_idx) > self.settings.min_match_len {
+                    self.matches.add_prefix(self.data, next_idx);
+                }
+
+                match lookahead {
+                    Some((next_pos, next_len)) if next_len > len => {
+                        self.pending = Some(Token::new_match(next_idx - next_pos, next_len));
+                        self.advance(1);
+                        return Some(Token::Literal(self.data[self.idx - 1]));
+                    }
+                    _ => Token::new_match(self.idx - pos, len),
+                }
+            }
         };
-        out.map(|i| {
-            self.advance(i.token_len());
-            i
-        })
+
+        self.advance(token.token_len());
+        Some(token)
     }
 }
 
@@ -178,34 +453,69 @@ impl<T: Iterator<Item=Token>> Decompressor<T> {
 
     pub fn decompress(mut self) -> Result<Vec<u8>, Error> {
         for token in self.source {
-            match token {
-                Token::Literal(l) => {
-                    if self.output_buf.len() + 1 > self.settings.max_output_len {
-                        return Err(Error::MaximumSizeExceeded);
-                    } else {
-                        self.output_buf.push(l)
-                    }
-                }
-                Token::Match { offset, length } => {
-                    if offset > self.output_buf.len() {
-                        return Err(Error::InvalidOffset { idx: self.output_buf.len(), offset });
-                    }
+            apply_token(&mut self.output_buf, token, self.settings.max_output_len)?;
+        }
 
-                    if self.output_buf.len() + length > self.settings.max_output_len {
-                        return Err(Error::MaximumSizeExceeded);
-                    }
+        Ok(self.output_buf)
+    }
+}
 
-                    let copy_start = self.output_buf.len() - offset;
-                    let copy_end = copy_start + length;
+/// Applies a single token to `output`, appending a literal byte or copying a match from
+/// earlier in `output`. Shared by [`Decompressor`] and the streaming decompressor so both
+/// enforce the same offset and size checks.
+pub(crate) fn apply_token(output: &mut Vec<u8>, token: Token, max_output_len: usize) -> Result<(), Error> {
+    match token {
+        Token::Literal(l) => {
+            if output.len() + 1 > max_output_len {
+                return Err(Error::MaximumSizeExceeded);
+            }
+            output.push(l);
+        }
+        Token::Match { offset, length } => {
+            if offset > output.len() {
+                return Err(Error::InvalidOffset { idx: output.len(), offset });
+            }
 
-                    for idx in copy_start..copy_end {
-                        self.output_buf.push(self.output_buf[idx])
-                    }
-                }
+            if output.len() + length > max_output_len {
+                return Err(Error::MaximumSizeExceeded);
             }
-        };
 
-        Ok(self.output_buf)
+            copy_match(output, offset, length);
+        }
+    }
+
+    Ok(())
+}
+
+/// The width, in bytes, of a single wide-copy run in [`copy_match`].
+const WILD_COPY_WIDTH: usize = 16;
+
+/// Appends `length` bytes read `offset` bytes back from the end of `output`.
+///
+/// When `offset >= WILD_COPY_WIDTH`, each `WILD_COPY_WIDTH`-byte run only reads bytes that
+/// are already in `output` when that run starts (its source lies `offset` bytes behind the
+/// write point, and a run is never longer than `offset`), though for `length > offset` those
+/// may be bytes an earlier run of this same call wrote; in that case the match is copied in such runs via
+/// `Vec::extend_from_within` (a safe equivalent of `copy_within` that can also grow the vec)
+/// instead of one bounds-checked push per byte. Otherwise (e.g. offset 1 repeating the previous
+/// byte, the classic RLE case) later bytes may need to copy from data this very call is writing,
+/// so it falls back to an ordered byte-by-byte copy.
+fn copy_match(output: &mut Vec<u8>, offset: usize, length: usize) {
+    let copy_start = output.len() - offset;
+
+    if offset >= WILD_COPY_WIDTH {
+        output.reserve(length);
+        let mut copied = 0;
+        while copied < length {
+            let take = WILD_COPY_WIDTH.min(length - copied);
+            let src = copy_start + copied;
+            output.extend_from_within(src..src + take);
+            copied += take;
+        }
+    } else {
+        for i in 0..length {
+            output.push(output[copy_start + i]);
+        }
     }
 }
 
@@ -219,6 +529,32 @@ pub fn decompress(tokens: impl Iterator<Item=Token>, settings: DecompressionSett
     dcmp.decompress()
 }
 
+/// Compresses `data` with `dict` preloaded into the match finder, so matches in `data` can
+/// point back into `dict` even though `dict` itself is never emitted. Useful for compressing
+/// many small, similar messages that share a dictionary, since it lets the very first bytes
+/// of each message find matches that an empty match finder wouldn't have seen yet.
+pub fn compress_with_dict(data: &[u8], dict: &[u8], settings: CompressionSettings) -> Vec<Token> {
+    let mut combined = dict.to_vec();
+    combined.extend_from_slice(data);
+
+    Compressor::with_start(&combined, dict.len(), settings).collect()
+}
+
+/// Decompresses `tokens` produced by [`compress_with_dict`] with the same `dict`, so a
+/// [`Token::Match`] whose offset reaches past the start of the real output resolves into the
+/// dictionary instead of failing with [`Error::InvalidOffset`].
+pub fn decompress_with_dict(tokens: impl Iterator<Item=Token>, dict: &[u8], settings: DecompressionSettings) -> Result<Vec<u8>, Error> {
+    let mut output = dict.to_vec();
+    let dict_len = output.len();
+    let max_output_len = settings.max_output_len() + dict_len;
+
+    for token in tokens {
+        apply_token(&mut output, token, max_output_len)?;
+    }
+
+    Ok(output.split_off(dict_len))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +607,47 @@ mod tests {
         assert!(matches!(err, Error::MaximumSizeExceeded))
     }
 
+    #[test]
+    fn decompression_wild_copy_periodic_below_width() {
+        // offset (10) < WILD_COPY_WIDTH (16): below the wide-copy threshold, so this takes
+        // the byte-by-byte path even though length (30) exceeds offset, which is exactly the
+        // periodic/overlapping case that path exists to handle correctly.
+        let inp: Vec<_> = vec![
+            Token::literals("0123456789"),
+            vec![Token::new_match(10, 30)],
+        ].into_iter().flatten().collect();
+
+        let out = decompress(inp.into_iter(), DecompressionSettings::default()).unwrap();
+        let expected = "0123456789".repeat(4);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn decompression_wild_copy_periodic_past_width() {
+        // offset (20) >= WILD_COPY_WIDTH (16), but offset < length (50): a long periodic match
+        // that's still safe to wide-copy, since each WILD_COPY_WIDTH-byte run only ever reads
+        // bytes at least `offset` behind the write point.
+        let pattern = "abcdefghijklmnopqrst"; // 20 bytes
+        let inp: Vec<_> = vec![
+            Token::literals(pattern),
+            vec![Token::new_match(20, 50)],
+        ].into_iter().flatten().collect();
+
+        let out = decompress(inp.into_iter(), DecompressionSettings::default()).unwrap();
+        let expected = pattern.repeat(4)[..70].to_string();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn decompression_wild_copy_rle_overlap() {
+        // offset (1) < length (20): classic run-length case where each new byte repeats the
+        // one immediately before it.
+        let inp: Vec<_> = vec![Token::Literal(b'x'), Token::new_match(1, 20)];
+
+        let out = decompress(inp.into_iter(), DecompressionSettings::default()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "x".repeat(21));
+    }
+
     #[test]
     fn compression_test() {
         let inp = "Abcdefgefgefg";
@@ -292,4 +669,84 @@ mod tests {
 
         assert_eq!(String::from_utf8(out).unwrap(), inp);
     }
+
+    #[test]
+    fn hc_compression_round_trips() {
+        let inp = "Abcdefgefgefgefgefgefgefgefgefgabcdefg";
+        let res = compress(inp, CompressionSettings::test_default_hc());
+        println!("{:?}", &res);
+        let out = decompress(res.into_iter(), DecompressionSettings::default())
+            .unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), inp);
+    }
+
+    #[test]
+    fn hc_large_compression_round_trips() {
+        let inp = std::fs::read_to_string("resources/asyoulik.txt").unwrap();
+        let res = compress(&inp, CompressionSettings::test_default_hc());
+        assert!(res.iter().any(|t| matches!(t, Token::Match { .. })));
+
+        let out = decompress(res.into_iter(), DecompressionSettings::default()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), inp);
+    }
+
+    #[test]
+    fn dict_compression_round_trips() {
+        let dict = "the quick brown fox jumps over the lazy dog";
+        let inp = "the quick brown fox is quick";
+
+        let tokens = compress_with_dict(inp.as_bytes(), dict.as_bytes(), CompressionSettings::test_default());
+        let out = decompress_with_dict(tokens.into_iter(), dict.as_bytes(), DecompressionSettings::default()).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), inp);
+    }
+
+    #[test]
+    fn dict_compression_finds_matches_into_the_dictionary() {
+        // Long enough to rule out the match coming from repetition within `inp` itself.
+        let dict = "a sentence that is reasonably unlikely to repeat by coincidence";
+        let inp = "a sentence that is reasonably unlikely to repeat by coincidence";
+
+        let tokens = compress_with_dict(inp.as_bytes(), dict.as_bytes(), CompressionSettings::test_default());
+        assert!(tokens.iter().any(|t| matches!(t, Token::Match { .. })));
+    }
+
+    #[test]
+    fn block_round_trip() {
+        let inp: Vec<_> = vec![
+            Token::literals("abcdef"),
+            vec![Token::new_match(4, 8)],
+            Token::literals("ABCD"),
+            vec![Token::new_match(1, 4)],
+        ].into_iter().flatten().collect();
+
+        let encoded = encode_block(&inp);
+        let decoded = decode_block(&encoded).unwrap();
+
+        let out = decompress(decoded.into_iter(), DecompressionSettings::default()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "abcdefcdefcdefABCDDDDD");
+    }
+
+    #[test]
+    fn block_round_trip_long_runs() {
+        // Force both the literal-count and match-length nibbles to saturate and
+        // exercise the LSIC extension bytes.
+        let mut tokens: Vec<Token> = Token::literals("a".repeat(300));
+        tokens.push(Token::new_match(1, 300));
+        tokens.extend(Token::literals("z".repeat(20)));
+
+        let encoded = encode_block(&tokens);
+        let decoded = decode_block(&encoded).unwrap();
+
+        let out = decompress(decoded.into_iter(), DecompressionSettings::default()).unwrap();
+        let expected = decompress(tokens.into_iter(), DecompressionSettings::default()).unwrap();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn decode_block_truncated() {
+        let err = decode_block(&[0x10]).unwrap_err();
+        assert!(matches!(err, Error::TruncatedBlock { .. }));
+    }
 }
\ No newline at end of file
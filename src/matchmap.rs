@@ -1,48 +1,142 @@
-use std::collections::{VecDeque, HashMap};
+//! A fixed-memory hash table used to find LZ77 match candidates.
+//!
+//! Unlike a `HashMap` keyed on borrowed slices, this table never allocates per prefix
+//! and holds no borrow of the input: it stores, per hash bucket, only the most recent
+//! position that produced it. Lookups re-verify the candidate's bytes against the
+//! current position (hash collisions are possible) and reject anything farther back
+//! than `max_match_offset` at lookup time, so there is nothing to cull as the window
+//! slides forward.
 
-/// Holds pointers to the locations of matches during a compression operation.
-/// The VecDeque allows us to remove older entries without needing to scan through the entire map.
+/// `2^TABLE_BITS` buckets, each holding one candidate position.
+const TABLE_BITS: u32 = 12;
+const TABLE_SIZE: usize = 1 << TABLE_BITS;
+
+/// Marks a bucket that has never been written.
+const EMPTY: u32 = u32::MAX;
+
+/// The length, in bytes, of the prefix hashed into a bucket.
+const PREFIX_LEN: usize = 4;
+
+fn hash(prefix: &[u8; PREFIX_LEN]) -> usize {
+    let v = u32::from_le_bytes(*prefix);
+    (v.wrapping_mul(2654435761) >> (32 - TABLE_BITS)) as usize
+}
+
+/// Holds pointers to the most recent position each 4-byte prefix was seen at.
 #[derive(Debug, Clone)]
-pub struct MatchMap<'a> {
-    entries: VecDeque<(&'a [u8], usize)>,
-    matches: HashMap<&'a [u8], usize>,
+pub struct MatchMap {
+    table: Box<[u32; TABLE_SIZE]>,
     max_match_offset: usize,
 }
 
-impl<'a> MatchMap<'a> {
+impl MatchMap {
     pub fn new(max_match_offset: usize) -> Self {
-        MatchMap { entries: VecDeque::new(), matches: HashMap::new(), max_match_offset }
+        MatchMap {
+            table: Box::new([EMPTY; TABLE_SIZE]),
+            max_match_offset,
+        }
     }
 
-    pub fn advance(&mut self, new_idx: usize) {
-        let cull_idx = new_idx.saturating_sub(self.max_match_offset);
-        let elems_to_remove = self.entries.iter()
-            .take_while(|(_, wh)| *wh < cull_idx)
-            .count();
-
-        let entries = &mut self.entries;
-        let matches = &mut self.matches;
-        entries.drain(..elems_to_remove)
-            .for_each(|f| {
-                if matches.contains_key(f.0) {
-                    if *matches.get(f.0).unwrap() < cull_idx {
-                        matches.remove(f.0);
-                    }
-                }
-            });
+    /// Looks up the most recent position with the same 4-byte prefix as `data[idx..]`,
+    /// verifying the candidate's bytes actually match (the table only stores a hash)
+    /// and that it's within `max_match_offset` of `idx`.
+    pub fn get_match(&self, data: &[u8], idx: usize) -> Option<usize> {
+        let prefix: &[u8; PREFIX_LEN] = data[idx..idx + PREFIX_LEN].try_into().unwrap();
+        let candidate = self.table[hash(prefix)];
+        if candidate == EMPTY {
+            return None;
+        }
+
+        let candidate = candidate as usize;
+        if idx - candidate > self.max_match_offset {
+            return None;
+        }
+
+        if &data[candidate..candidate + PREFIX_LEN] == prefix {
+            Some(candidate)
+        } else {
+            None
+        }
     }
 
-    pub fn get_match(&self, item: &[u8]) -> Option<usize> {
-        self.matches.get(item).cloned()
+    /// Records `idx` as the most recent position whose 4-byte prefix is `data[idx..]`.
+    pub fn add_prefix(&mut self, data: &[u8], idx: usize) {
+        let prefix: &[u8; PREFIX_LEN] = data[idx..idx + PREFIX_LEN].try_into().unwrap();
+        self.table[hash(prefix)] = idx as u32;
     }
+}
+
+/// A hash-chain match finder for LZ4HC-style high-compression search.
+///
+/// Unlike [`MatchMap`], which keeps only the single most recent position per bucket,
+/// `HashChain` also keeps a `prev` link from each position back to the previous position
+/// with the same 4-byte prefix, so a lookup can walk the whole chain of candidates (bounded
+/// by a caller-supplied `max_chain_length`) and keep the longest one found, rather than
+/// settling for the first.
+#[derive(Debug, Clone)]
+pub struct HashChain {
+    head: Box<[u32; TABLE_SIZE]>,
+    prev: Vec<u32>,
+    max_match_offset: usize,
+}
 
-    pub fn reset(&mut self) {
-        self.matches.clear();
-        self.entries.clear();
+impl HashChain {
+    pub fn new(max_match_offset: usize) -> Self {
+        HashChain {
+            head: Box::new([EMPTY; TABLE_SIZE]),
+            prev: Vec::new(),
+            max_match_offset,
+        }
+    }
+
+    /// Records `idx` as the most recent position whose 4-byte prefix is `data[idx..]`,
+    /// linking it to whatever position previously held that bucket.
+    pub fn add_prefix(&mut self, data: &[u8], idx: usize) {
+        let prefix: &[u8; PREFIX_LEN] = data[idx..idx + PREFIX_LEN].try_into().unwrap();
+        let bucket = hash(prefix);
+
+        if self.prev.len() <= idx {
+            self.prev.resize(idx + 1, EMPTY);
+        }
+        self.prev[idx] = self.head[bucket];
+        self.head[bucket] = idx as u32;
     }
 
-    pub fn add_prefix<'b : 'a>(&mut self, item: &'b [u8], idx: usize) {
-        self.matches.insert(item, idx);
-        self.entries.push_back((item, idx));
+    /// Walks up to `max_chain_length` candidates sharing `data[idx..]`'s 4-byte prefix and
+    /// returns the longest verified match within `max_match_offset`, if any.
+    pub fn best_match(&self, data: &[u8], idx: usize, max_chain_length: usize) -> Option<(usize, usize)> {
+        let prefix: &[u8; PREFIX_LEN] = data[idx..idx + PREFIX_LEN].try_into().unwrap();
+        let mut candidate = self.head[hash(prefix)];
+        let mut best: Option<(usize, usize)> = None;
+
+        for _ in 0..max_chain_length {
+            if candidate == EMPTY {
+                break;
+            }
+            let pos = candidate as usize;
+            if idx - pos > self.max_match_offset {
+                // Chain entries only get older going forward, so nothing further back helps.
+                break;
+            }
+
+            if &data[pos..pos + PREFIX_LEN] == prefix {
+                let len = match_length(data, idx, pos);
+                if best.map_or(true, |(_, best_len)| len > best_len) {
+                    best = Some((pos, len));
+                }
+            }
+
+            candidate = self.prev.get(pos).copied().unwrap_or(EMPTY);
+        }
+
+        best
     }
-}
\ No newline at end of file
+}
+
+/// The number of bytes that match between `data[a..]` and `data[b..]`.
+pub(crate) fn match_length(data: &[u8], a: usize, b: usize) -> usize {
+    data[a..].iter()
+        .zip(data[b..].iter())
+        .take_while(|(&x, &y)| x == y)
+        .count()
+}
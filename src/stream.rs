@@ -0,0 +1,132 @@
+//! Streaming compression and decompression over bounded blocks.
+//!
+//! [`Compressor`](crate::lz77::Compressor) requires its entire input up front, which rules
+//! out data that arrives incrementally or doesn't fit in memory. [`StreamingCompressor`] and
+//! [`StreamingDecompressor`] process input one block at a time instead, while preserving
+//! cross-block back-references by retaining a dictionary window of the last
+//! `max_match_offset` bytes: after compressing a block, its tail is carried forward and fed
+//! back into the match finder so the next block's matches can still reach into it.
+
+use crate::lz77::{self, CompressionSettings, DecompressionSettings, Error, Token};
+
+/// Compresses input a block at a time, carrying a trailing window of previously-pushed
+/// bytes forward so matches in later blocks can reference earlier ones.
+pub struct StreamingCompressor {
+    settings: CompressionSettings,
+    window: Vec<u8>,
+}
+
+impl StreamingCompressor {
+    /// Creates a streaming compressor that will use `settings` for every block.
+    pub fn new(settings: CompressionSettings) -> Self {
+        StreamingCompressor {
+            settings,
+            window: Vec::new(),
+        }
+    }
+
+    /// Compresses `chunk` and returns it as a complete encoded block; matches within the
+    /// block may reach back into data from previous calls to `push` via the retained window.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let mut combined = std::mem::take(&mut self.window);
+        let window_len = combined.len();
+        combined.extend_from_slice(chunk);
+
+        let tokens: Vec<Token> = lz77::Compressor::with_start(&combined, window_len, self.settings).collect();
+        let encoded = lz77::encode_block(&tokens);
+
+        let max_offset = self.settings.max_match_offset();
+        self.window = if combined.len() > max_offset {
+            combined[combined.len() - max_offset..].to_vec()
+        } else {
+            combined
+        };
+
+        encoded
+    }
+
+    /// Finalizes the stream. Each `push` already produces a complete block,
+    /// so there's nothing buffered to flush; provided for a symmetric push/finish lifecycle.
+    pub fn finish(self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+/// Decompresses a sequence of blocks produced by [`StreamingCompressor`], keeping the
+/// decompressed output from earlier blocks around so a [`Token::Match`] can reach back
+/// across block boundaries.
+pub struct StreamingDecompressor {
+    settings: DecompressionSettings,
+    output: Vec<u8>,
+}
+
+impl StreamingDecompressor {
+    /// Creates a streaming decompressor bounded by `settings.max_output_len` across the
+    /// whole stream, not just a single block.
+    pub fn new(settings: DecompressionSettings) -> Self {
+        StreamingDecompressor {
+            settings,
+            output: Vec::new(),
+        }
+    }
+
+    /// Decodes `block` and appends its output, returning the slice of output produced by
+    /// this call.
+    pub fn push(&mut self, block: &[u8]) -> Result<&[u8], Error> {
+        let before = self.output.len();
+
+        let tokens = lz77::decode_block(block)?;
+        for token in tokens {
+            lz77::apply_token(&mut self.output, token, self.settings.max_output_len())?;
+        }
+
+        Ok(&self.output[before..])
+    }
+
+    /// The complete output decompressed so far.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lz77::CompressionSettings;
+
+    #[test]
+    fn streams_match_across_block_boundaries() {
+        let first = "The quick brown fox jumps over the lazy dog. ";
+        let second = "The quick brown fox jumps over the lazy dog again.";
+
+        let mut compressor = StreamingCompressor::new(CompressionSettings::test_default());
+        let block_a = compressor.push(first.as_bytes());
+        let block_b = compressor.push(second.as_bytes());
+
+        let mut decompressor = StreamingDecompressor::new(DecompressionSettings::default());
+        decompressor.push(&block_a).unwrap();
+        decompressor.push(&block_b).unwrap();
+
+        let expected = format!("{first}{second}");
+        assert_eq!(decompressor.output(), expected.as_bytes());
+    }
+
+    #[test]
+    fn window_stays_bounded_across_many_blocks() {
+        // Pushes far more data than max_match_offset across many small blocks, so the
+        // retained window must be truncated rather than growing without bound.
+        let mut compressor = StreamingCompressor::new(CompressionSettings::test_default());
+        let mut decompressor = StreamingDecompressor::new(DecompressionSettings::default());
+        let mut expected = Vec::new();
+
+        for i in 0..20 {
+            let chunk = format!("chunk {i} ").repeat(1000);
+            let block = compressor.push(chunk.as_bytes());
+            decompressor.push(&block).unwrap();
+            expected.extend_from_slice(chunk.as_bytes());
+        }
+
+        assert_eq!(decompressor.output(), expected.as_slice());
+        assert!(compressor.window.len() <= CompressionSettings::test_default().max_match_offset());
+    }
+}
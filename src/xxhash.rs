@@ -0,0 +1,83 @@
+//! A small, self-contained implementation of the 32-bit xxHash algorithm, used by
+//! [`crate::frame`] for block and content checksums.
+
+const PRIME32_1: u32 = 0x9E3779B1;
+const PRIME32_2: u32 = 0x85EBCA77;
+const PRIME32_3: u32 = 0xC2B2AE3D;
+const PRIME32_4: u32 = 0x27D4EB2F;
+const PRIME32_5: u32 = 0x165667B1;
+
+fn round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(PRIME32_2))
+        .rotate_left(13)
+        .wrapping_mul(PRIME32_1)
+}
+
+/// Computes the 32-bit xxHash of `input` with the given `seed`.
+pub(crate) fn xxhash32(input: &[u8], seed: u32) -> u32 {
+    let mut data = input;
+    let mut h32;
+
+    if data.len() >= 16 {
+        let mut v1 = seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2);
+        let mut v2 = seed.wrapping_add(PRIME32_2);
+        let mut v3 = seed;
+        let mut v4 = seed.wrapping_sub(PRIME32_1);
+
+        while data.len() >= 16 {
+            v1 = round(v1, u32::from_le_bytes(data[0..4].try_into().unwrap()));
+            v2 = round(v2, u32::from_le_bytes(data[4..8].try_into().unwrap()));
+            v3 = round(v3, u32::from_le_bytes(data[8..12].try_into().unwrap()));
+            v4 = round(v4, u32::from_le_bytes(data[12..16].try_into().unwrap()));
+            data = &data[16..];
+        }
+
+        h32 = v1
+            .rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18));
+    } else {
+        h32 = seed.wrapping_add(PRIME32_5);
+    }
+
+    h32 = h32.wrapping_add(input.len() as u32);
+
+    while data.len() >= 4 {
+        h32 = h32.wrapping_add(u32::from_le_bytes(data[0..4].try_into().unwrap()).wrapping_mul(PRIME32_3));
+        h32 = h32.rotate_left(17).wrapping_mul(PRIME32_4);
+        data = &data[4..];
+    }
+
+    for &byte in data {
+        h32 = h32.wrapping_add((byte as u32).wrapping_mul(PRIME32_5));
+        h32 = h32.rotate_left(11).wrapping_mul(PRIME32_1);
+    }
+
+    h32 ^= h32 >> 15;
+    h32 = h32.wrapping_mul(PRIME32_2);
+    h32 ^= h32 >> 13;
+    h32 = h32.wrapping_mul(PRIME32_3);
+    h32 ^= h32 >> 16;
+
+    h32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_matches_known_value() {
+        // xxh32("", seed=0) is a well-known test vector.
+        assert_eq!(xxhash32(&[], 0), 0x02CC5D05);
+    }
+
+    #[test]
+    fn nonempty_input_is_deterministic() {
+        let a = xxhash32(b"Hello, world!", 0);
+        let b = xxhash32(b"Hello, world!", 0);
+        assert_eq!(a, b);
+        assert_ne!(a, xxhash32(b"Hello, world?", 0));
+    }
+}